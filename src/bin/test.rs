@@ -1,18 +1,70 @@
 // This test program is used in the tests in src/lib.rs.
 use std::env;
 use std::io::{self, Read};
+use std::slice;
+
+/// Allocate `size` bytes such that the last byte sits right at the end of a
+/// mapping, with nothing readable directly after it.
+#[cfg(unix)]
+fn alloc_at_end_of_mapping(size: usize) -> &'static mut [u8] {
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let len = (size + page - 1) / page * page;
+    unsafe {
+        let base = libc::mmap(
+            std::ptr::null_mut(),
+            len + page,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANON,
+            -1,
+            0,
+        );
+        assert_ne!(base, libc::MAP_FAILED, "mmap failed");
+        let base = base as *mut u8;
+        assert_eq!(
+            libc::munmap(base.add(len) as *mut _, page),
+            0,
+            "munmap failed"
+        );
+        slice::from_raw_parts_mut(base.add(len - size), size)
+    }
+}
+
+/// Allocate `size` bytes such that the last byte sits right at the end of a
+/// mapping, with nothing readable directly after it.
+#[cfg(windows)]
+fn alloc_at_end_of_mapping(size: usize) -> &'static mut [u8] {
+    use winapi::um::memoryapi::VirtualAlloc;
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RESERVE, PAGE_NOACCESS, PAGE_READWRITE};
+
+    const PAGE: usize = 4096;
+    let len = (size + PAGE - 1) / PAGE * PAGE;
+    unsafe {
+        // Reserve one page more than we need, but only commit the pages
+        // holding the data so the trailing page is inaccessible.
+        let base = VirtualAlloc(std::ptr::null_mut(), len + PAGE, MEM_RESERVE, PAGE_NOACCESS);
+        assert!(!base.is_null(), "VirtualAlloc(MEM_RESERVE) failed");
+        let base = VirtualAlloc(base, len, MEM_COMMIT, PAGE_READWRITE) as *mut u8;
+        assert!(!base.is_null(), "VirtualAlloc(MEM_COMMIT) failed");
+        slice::from_raw_parts_mut(base.add(len - size), size)
+    }
+}
 
 fn main() {
     let size = env::args()
         .nth(1)
         .and_then(|a| a.parse::<usize>().ok())
         .unwrap_or(32);
-    let data = if size <= u8::max_value() as usize {
-        (0..size as u8).collect::<Vec<u8>>()
+    let pattern = (0..size).map(|v| (v % (u8::MAX as usize + 1)) as u8);
+    let owned;
+    let data: &[u8] = if env::args().nth(2).as_deref() == Some("end-of-mapping") {
+        let data = alloc_at_end_of_mapping(size);
+        for (dest, v) in data.iter_mut().zip(pattern) {
+            *dest = v;
+        }
+        data
     } else {
-        (0..size)
-            .map(|v| (v % (u8::max_value() as usize + 1)) as u8)
-            .collect::<Vec<u8>>()
+        owned = pattern.collect::<Vec<u8>>();
+        &owned
     };
     println!("{:p} {}", data.as_ptr(), data.len());
     // Wait to exit until stdin is closed.
@@ -2,7 +2,15 @@
 //!
 //! This crate provides a trait—[`CopyAddress`](trait.CopyAddress.html),
 //! and a helper function—[`copy_address`](fn.copy_address.html) that
-//! allow reading memory from another process.
+//! allow reading memory from another process, as well as a
+//! [`PutAddress`](trait.PutAddress.html) trait and the
+//! [`put_address`](fn.put_address.html) and
+//! [`write_address`](fn.write_address.html) helpers for writing it.
+//! [`copy_value`](fn.copy_value.html) and
+//! [`copy_array`](fn.copy_array.html) read typed values, for types that
+//! implement [`Pod`](trait.Pod.html).
+//! [`ProcessMemoryReader`](struct.ProcessMemoryReader.html) adapts
+//! `CopyAddress` to `std::io::Read` and `std::io::Seek`.
 //!
 //! Note: you may not always have permission to read memory from another
 //! process! This may require `sudo` on some systems, and may fail even with
@@ -27,13 +35,273 @@
 #[doc = include_str!("../README.md")]
 mod readme {}
 
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
 use std::io;
+use std::path::PathBuf;
 
 /// A trait that provides a method for reading memory from another process.
 pub trait CopyAddress {
     /// Try to copy `buf.len()` bytes from `addr` in the process `self`, placing
     /// them in `buf`.
     fn copy_address(&self, addr: usize, buf: &mut [u8]) -> io::Result<()>;
+
+    /// Try to copy up to `buf.len()` bytes from `addr` in the process `self`,
+    /// returning the number of bytes placed at the start of `buf`.
+    ///
+    /// Unlike `copy_address`, this succeeds when only the beginning of the
+    /// range is readable (for example when it runs off the end of a mapping),
+    /// and only fails if not a single byte could be read.
+    ///
+    /// The default implementation simply calls `copy_address`, so it either
+    /// fills the whole buffer or fails.
+    fn copy_address_partial(&self, addr: usize, buf: &mut [u8]) -> io::Result<usize> {
+        self.copy_address(addr, buf).map(|()| buf.len())
+    }
+
+    /// Try to perform several reads at once, copying `buf.len()` bytes from
+    /// `addr` into `buf` for every `(addr, buf)` pair in `requests`.
+    ///
+    /// Every request is attempted even if some of them fail. If any of them
+    /// did, the returned error wraps a [`BatchError`](struct.BatchError.html)
+    /// listing the index and cause of each failed request, so that only
+    /// those need to be retried. All other buffers have been filled.
+    ///
+    /// This is a wrapper around `copy_address_vectored`, so batches of any
+    /// size are fine on Linux too.
+    fn copy_address_batch(&self, requests: &mut [(usize, &mut [u8])]) -> io::Result<()> {
+        let mut reads = requests
+            .iter_mut()
+            .map(|(addr, buf)| ReadOp::new(*addr, buf))
+            .collect::<Vec<_>>();
+        self.copy_address_vectored(&mut reads)?;
+        let failures = reads
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, read)| read.error.map(|e| (i, e)))
+            .collect();
+        BatchError::check(failures)
+    }
+
+    /// Try to perform all of `reads`, recording the outcome of each one in
+    /// its [`ReadOp::error`](struct.ReadOp.html#structfield.error) field.
+    ///
+    /// A failing read does not stop the rest of the batch from being
+    /// attempted. An error is only returned if the batch as a whole could not
+    /// be attempted.
+    ///
+    /// The default implementation simply calls `copy_address` for each read
+    /// in turn. On Linux the reads are gathered into as few
+    /// `process_vm_readv` calls as possible instead, which is much cheaper
+    /// for many small reads. The kernel accepts at most `IOV_MAX` (1024)
+    /// iovecs per call, so larger batches are split into chunks of that
+    /// many reads, one call each.
+    fn copy_address_vectored(&self, reads: &mut [ReadOp<'_>]) -> io::Result<()> {
+        for read in reads {
+            read.error = self.copy_address(read.addr, read.buf).err();
+        }
+        Ok(())
+    }
+}
+
+/// A single read performed by
+/// [`CopyAddress::copy_address_vectored`](trait.CopyAddress.html#method.copy_address_vectored).
+#[derive(Debug)]
+pub struct ReadOp<'a> {
+    /// The address to read from in the other process.
+    pub addr: usize,
+    /// The buffer to read `buf.len()` bytes into.
+    pub buf: &'a mut [u8],
+    /// The reason this read failed, or `None` if it succeeded (or has not
+    /// been performed yet).
+    pub error: Option<io::Error>,
+}
+
+impl<'a> ReadOp<'a> {
+    /// Create a read of `buf.len()` bytes from `addr` into `buf`.
+    pub fn new(addr: usize, buf: &'a mut [u8]) -> Self {
+        ReadOp {
+            addr,
+            buf,
+            error: None,
+        }
+    }
+}
+
+/// The error returned by
+/// [`CopyAddress::copy_address_batch`](trait.CopyAddress.html#method.copy_address_batch)
+/// when some of the requested reads failed.
+///
+/// This is wrapped in an `io::Error` of kind `Other`, and can be retrieved
+/// with `io::Error::get_ref` and `downcast_ref`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io;
+/// use read_process_memory::*;
+///
+/// # fn foo(handle: &ProcessHandle, a: usize, b: usize) -> io::Result<()> {
+/// let mut first = [0u8; 8];
+/// let mut second = [0u8; 8];
+/// let mut requests = [(a, &mut first[..]), (b, &mut second[..])];
+/// if let Err(e) = handle.copy_address_batch(&mut requests) {
+///     match e.get_ref().and_then(|e| e.downcast_ref::<BatchError>()) {
+///         Some(batch) => {
+///             for (index, error) in batch.failures() {
+///                 println!("request {} failed: {}", index, error);
+///             }
+///         }
+///         None => return Err(e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BatchError {
+    failures: Vec<(usize, io::Error)>,
+}
+
+impl BatchError {
+    /// The index into the batch and the error of each request that failed,
+    /// in ascending order of index.
+    pub fn failures(&self) -> &[(usize, io::Error)] {
+        &self.failures
+    }
+
+    /// Turn a list of failures into the result of a batch read.
+    fn check(failures: Vec<(usize, io::Error)>) -> io::Result<()> {
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                BatchError { failures },
+            ))
+        }
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} read(s) in batch failed", self.failures.len())?;
+        if let Some((index, error)) = self.failures.first() {
+            write!(f, ", first at index {}: {}", index, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for BatchError {}
+
+/// A trait that provides a method for writing memory into another process.
+pub trait PutAddress {
+    /// Try to write all of `buf` to `addr` in the process `self`.
+    fn put_address(&self, addr: usize, buf: &[u8]) -> io::Result<()>;
+}
+
+/// A region of memory mapped into another process' address space, as
+/// returned by `ProcessHandle::memory_regions`.
+///
+/// Regions are listed in ascending order of address and only cover memory
+/// that is actually backed by something: address space that is merely
+/// reserved is left out. The permission flags describe the current
+/// protection of the region (not the maximum it could be changed to), and
+/// mean the same on every platform: copy-on-write memory counts as writable,
+/// and inaccessible mappings are reported with all three flags unset.
+///
+/// Only Windows leaves out guard pages (`PAGE_GUARD`). Elsewhere guard
+/// mappings, such as those below thread stacks, are ordinary inaccessible
+/// mappings and are listed with all three flags unset.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryRegion {
+    /// The address of the first byte of the region.
+    pub start: usize,
+    /// The size of the region in bytes.
+    pub size: usize,
+    /// Whether the process may read this region.
+    pub readable: bool,
+    /// Whether the process may write to this region.
+    pub writable: bool,
+    /// Whether the process may execute code from this region.
+    pub executable: bool,
+    /// The path of the file mapped into this region, if any and if the
+    /// platform reports it. On Windows this is an NT device path such as
+    /// `\Device\HarddiskVolume1\Windows\System32\ntdll.dll`.
+    pub path: Option<PathBuf>,
+}
+
+impl MemoryRegion {
+    /// The address one past the last byte of the region.
+    pub fn end(&self) -> usize {
+        self.start + self.size
+    }
+
+    /// Whether `addr` lies within this region.
+    pub fn contains(&self, addr: usize) -> bool {
+        self.start <= addr && addr < self.end()
+    }
+}
+
+/// The granularity used when falling back to reading a range piecewise to
+/// find out how much of it is readable. Page sizes are a multiple of this
+/// everywhere we care about, so chunks never straddle a page boundary.
+#[cfg(any(target_os = "macos", target_os = "freebsd", windows))]
+const PARTIAL_READ_CHUNK: usize = 4096;
+
+/// Implement a strict `copy_address` on top of `copy_address_partial`,
+/// failing unless the whole of `buf` could be read.
+///
+/// This is only used where a partial read is a single call anyway.
+#[cfg(target_os = "linux")]
+fn copy_address_exact<T>(source: &T, addr: usize, buf: &mut [u8]) -> io::Result<()>
+where
+    T: CopyAddress + ?Sized,
+{
+    let read = source.copy_address_partial(addr, buf)?;
+    if read != buf.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "Only {} of {} bytes at {:x} are readable",
+                read,
+                buf.len(),
+                addr
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Read as many bytes as possible from `addr` by issuing strict reads of
+/// aligned chunks with `read`, stopping at the first chunk that cannot be
+/// read.
+///
+/// This is used on platforms where the OS reports a failure for the whole
+/// range rather than a byte count when only part of it is readable.
+#[cfg(any(target_os = "macos", target_os = "freebsd", windows))]
+fn copy_address_chunked<F>(mut read: F, addr: usize, buf: &mut [u8]) -> io::Result<usize>
+where
+    F: FnMut(usize, &mut [u8]) -> io::Result<()>,
+{
+    let mut copied = 0;
+    while copied < buf.len() {
+        // The range may run off the end of the address space, which nothing
+        // can be read from anyway.
+        let start = match addr.checked_add(copied) {
+            Some(start) => start,
+            None => break,
+        };
+        let len = (PARTIAL_READ_CHUNK - start % PARTIAL_READ_CHUNK).min(buf.len() - copied);
+        match read(start, &mut buf[copied..copied + len]) {
+            Ok(()) => copied += len,
+            Err(e) if copied == 0 => return Err(e),
+            Err(_) => break,
+        }
+    }
+    Ok(copied)
 }
 
 /// A process ID.
@@ -55,36 +323,198 @@ pub use crate::platform::Pid;
 /// }
 /// ```
 ///
+/// The memory mapped into the process can be listed with
+/// `ProcessHandle::memory_regions`, which returns a `Vec` of
+/// [`MemoryRegion`](struct.MemoryRegion.html).
+///
 /// This operation is not guaranteed to succeed. Specifically, on Windows
-/// `OpenProcess` may fail. On macOS `task_for_pid` will generally fail
+/// `OpenProcess` may fail; `ProcessHandle::open_with_access` can be used to
+/// ask for fewer (or more) access rights. On macOS `task_for_pid` will generally fail
 /// unless run as root, and even then it may fail when called on certain
 /// programs; it may however run without root on the current process.
 pub use crate::platform::ProcessHandle;
 
+impl ProcessHandle {
+    /// Spawn `cmd` and get a handle to the new process.
+    ///
+    /// This is just `Command::spawn` followed by
+    /// `ProcessHandle::try_from(&child)`, so on macOS it generally needs root
+    /// like `task_for_pid` does. If no handle can be obtained the child is
+    /// killed and reaped again, and the error is returned.
+    pub fn spawn(cmd: &mut std::process::Command) -> io::Result<(std::process::Child, Self)> {
+        let mut child = cmd.spawn()?;
+        match Self::try_from(&child) {
+            Ok(handle) => Ok((child, handle)),
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                Err(e)
+            }
+        }
+    }
+}
+
 #[cfg(target_os = "linux")]
 mod platform {
-    use libc::{c_void, iovec, pid_t, process_vm_readv};
+    use libc::{c_void, iovec, pid_t, process_vm_readv, process_vm_writev};
     use std::convert::TryFrom;
+    use std::ffi::OsStr;
     use std::fs;
     use std::io;
-    use std::io::Read;
-    use std::io::Seek;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::io::RawFd;
+    use std::path::PathBuf;
     use std::process::Child;
+    use std::str;
+    use std::sync::{Arc, Mutex};
 
-    use super::CopyAddress;
+    use super::{copy_address_exact, CopyAddress, MemoryRegion, PutAddress, ReadOp};
 
     /// On Linux a `Pid` is just a `libc::pid_t`.
     pub type Pid = pid_t;
-    /// On Linux a `ProcessHandle` is just a `libc::pid_t`.
+    /// On Linux a `ProcessHandle` is a `libc::pid_t`, along with a pidfd
+    /// for it where the kernel supports them, and `/proc/$pid/mem` once it
+    /// has been needed.
+    ///
+    /// Clones share the same pidfd and `/proc/$pid/mem` files.
     #[derive(Clone)]
-    pub struct ProcessHandle(Pid);
+    pub struct ProcessHandle {
+        pid: Pid,
+        /// A pidfd referring to the process, used to tell whether it is still
+        /// alive. Since the pid can't be reused until the process exits, a
+        /// read that finds the process alive afterwards read from the right
+        /// process. `None` on kernels older than 5.3.
+        pidfd: Option<Arc<Pidfd>>,
+        /// `/proc/$pid/mem`, opened the first time a read has to fall back to
+        /// it and kept open from then on, so that subsequent reads don't have
+        /// to reopen it. It stays tied to the process it was opened for, so
+        /// once that exits reads from it fail rather than reading whatever
+        /// process reuses the pid.
+        proc_mem: Arc<Mutex<Option<Arc<fs::File>>>>,
+        /// `/proc/$pid/mem` opened for writing, the same way as `proc_mem`.
+        proc_mem_writable: Arc<Mutex<Option<Arc<fs::File>>>>,
+        /// Always read and write through `/proc/$pid/mem`, for testing the fallback.
+        #[cfg(test)]
+        force_proc_mem: bool,
+    }
+
+    /// An owned pidfd, closed when dropped.
+    struct Pidfd(RawFd);
+
+    impl Pidfd {
+        /// Call `pidfd_open`, returning `None` if the kernel doesn't support
+        /// it, we aren't allowed to use it or `pid` is not a process but a
+        /// thread.
+        fn open(pid: Pid) -> io::Result<Option<Self>> {
+            let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+            if fd == -1 {
+                let e = io::Error::last_os_error();
+                match e.raw_os_error() {
+                    // No pidfd support, or seccomp filters denying it.
+                    Some(libc::ENOSYS) | Some(libc::EPERM) | Some(libc::EACCES) => Ok(None),
+                    // `pid` is a thread other than the thread group leader,
+                    // which pidfds can't refer to (`ENOENT` since Linux 6.9,
+                    // `EINVAL` before) but which can still be read from.
+                    Some(libc::ENOENT) | Some(libc::EINVAL) => Ok(None),
+                    _ => Err(e),
+                }
+            } else {
+                Ok(Some(Pidfd(fd as RawFd)))
+            }
+        }
+
+        /// A pidfd becomes readable once its process has exited.
+        fn is_alive(&self) -> bool {
+            let mut pollfd = libc::pollfd {
+                fd: self.0,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            loop {
+                match unsafe { libc::poll(&mut pollfd, 1, 0) } {
+                    0 => return true,
+                    -1 if io::Error::last_os_error().raw_os_error() == Some(libc::EINTR) => {}
+                    _ => return false,
+                }
+            }
+        }
+    }
+
+    impl Drop for Pidfd {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.0) };
+        }
+    }
 
     /// On Linux, process handle is a pid.
+    ///
+    /// This also opens a pidfd for the process where the kernel supports it
+    /// (Linux 5.3 and later), so that reads fail once the process exits
+    /// instead of reading from whatever process reuses its pid. With a pidfd
+    /// this fails if there is no process `pid`.
     impl TryFrom<Pid> for ProcessHandle {
         type Error = io::Error;
 
         fn try_from(pid: Pid) -> io::Result<Self> {
-            Ok(Self(pid))
+            Ok(Self {
+                pid,
+                pidfd: Pidfd::open(pid)?.map(Arc::new),
+                proc_mem: Arc::new(Mutex::new(None)),
+                proc_mem_writable: Arc::new(Mutex::new(None)),
+                #[cfg(test)]
+                force_proc_mem: false,
+            })
+        }
+    }
+
+    impl ProcessHandle {
+        /// Check whether the process is still running.
+        ///
+        /// This uses the pidfd where available. Otherwise it checks whether
+        /// there is any process with this pid, which may be a different one
+        /// if the original exited and the pid was reused.
+        pub fn is_alive(&self) -> bool {
+            match self.pidfd {
+                Some(ref pidfd) => pidfd.is_alive(),
+                None => {
+                    let result = unsafe { libc::kill(self.pid, 0) };
+                    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+                }
+            }
+        }
+
+        /// Fail with `ESRCH` if the process has exited, since whatever was
+        /// just read may have come from another process reusing the pid.
+        fn check_alive(&self) -> io::Result<()> {
+            match self.pidfd {
+                Some(ref pidfd) if !pidfd.is_alive() => {
+                    Err(io::Error::from_raw_os_error(libc::ESRCH))
+                }
+                _ => Ok(()),
+            }
+        }
+
+        /// Always read and write through `/proc/$pid/mem` instead of
+        /// `process_vm_readv` and `process_vm_writev`.
+        #[cfg(test)]
+        pub(crate) fn force_proc_mem(&mut self) {
+            self.force_proc_mem = true;
+        }
+
+        /// Forget the pidfd, as on kernels without pidfd support.
+        #[cfg(test)]
+        pub(crate) fn without_pidfd(&mut self) {
+            self.pidfd = None;
+        }
+
+        /// Open `pid` for both reading and writing its memory.
+        ///
+        /// Handles need no special access rights to write here, so this is
+        /// the same as converting from a `Pid`. It exists for symmetry with
+        /// Windows.
+        pub fn open_writable(pid: Pid) -> io::Result<Self> {
+            Self::try_from(pid)
         }
     }
 
@@ -99,6 +529,19 @@ mod platform {
 
     impl CopyAddress for ProcessHandle {
         fn copy_address(&self, addr: usize, buf: &mut [u8]) -> io::Result<()> {
+            copy_address_exact(self, addr, buf)
+        }
+
+        fn copy_address_partial(&self, addr: usize, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            #[cfg(test)]
+            {
+                if self.force_proc_mem {
+                    return self.read_proc_mem(addr, buf);
+                }
+            }
             let local_iov = iovec {
                 iov_base: buf.as_mut_ptr() as *mut c_void,
                 iov_len: buf.len(),
@@ -107,37 +550,322 @@ mod platform {
                 iov_base: addr as *mut c_void,
                 iov_len: buf.len(),
             };
-            let result = unsafe { process_vm_readv(self.0, &local_iov, 1, &remote_iov, 1, 0) };
+            // `process_vm_readv` stops at the first unreadable byte and
+            // returns how much it managed to copy before that.
+            let result = unsafe { process_vm_readv(self.pid, &local_iov, 1, &remote_iov, 1, 0) };
             if result == -1 {
                 match io::Error::last_os_error().raw_os_error() {
                     Some(libc::ENOSYS) | Some(libc::EPERM) => {
                         // fallback to reading /proc/$pid/mem if kernel does not
                         // implement process_vm_readv()
-                        let mut procmem = fs::File::open(format!("/proc/{}/mem", self.0))?;
-                        procmem.seek(io::SeekFrom::Start(addr as u64))?;
-                        procmem.read_exact(buf)
+                        self.read_proc_mem(addr, buf)
+                    }
+                    _ => Err(io::Error::last_os_error()),
+                }
+            } else {
+                self.check_alive()?;
+                Ok(result as usize)
+            }
+        }
+
+        fn copy_address_vectored(&self, reads: &mut [ReadOp<'_>]) -> io::Result<()> {
+            #[cfg(test)]
+            {
+                if self.force_proc_mem {
+                    for read in reads {
+                        read.error = self.copy_address(read.addr, read.buf).err();
+                    }
+                    return Ok(());
+                }
+            }
+            let mut start = 0;
+            while start < reads.len() {
+                let end = reads.len().min(start + IOV_MAX);
+                let chunk = &mut reads[start..end];
+                match self.readv_chunk(chunk) {
+                    Ok(done) if done == chunk.len() => {
+                        start = end;
+                        continue;
+                    }
+                    Ok(done) => start += done,
+                    Err(e) => match e.raw_os_error() {
+                        // `process_vm_readv` is unavailable altogether, so
+                        // read the rest one by one with the fallback.
+                        Some(libc::ENOSYS) | Some(libc::EPERM) => break,
+                        // The process is gone, or the call itself is bad, so
+                        // no read in the batch can succeed.
+                        Some(libc::ESRCH) | Some(libc::EINVAL) => return Err(e),
+                        _ => {}
+                    },
+                }
+                // Read the offending request on its own to find out why it
+                // failed, then carry on with the batch after it.
+                let read = &mut reads[start];
+                read.error = self.copy_address(read.addr, read.buf).err();
+                start += 1;
+            }
+            for read in &mut reads[start..] {
+                read.error = self.copy_address(read.addr, read.buf).err();
+            }
+            Ok(())
+        }
+    }
+
+    impl PutAddress for ProcessHandle {
+        fn put_address(&self, addr: usize, buf: &[u8]) -> io::Result<()> {
+            // With a pidfd, write through `/proc/$pid/mem` instead, since
+            // `proc_mem_file` makes sure that is tied to the right process, so
+            // the write can't end up in whatever process reuses the pid.
+            if self.pidfd.is_some() {
+                return self.write_proc_mem(addr, buf);
+            }
+            #[cfg(test)]
+            {
+                if self.force_proc_mem {
+                    return self.write_proc_mem(addr, buf);
+                }
+            }
+            // Without a pidfd there's no telling whether `pid` still refers to
+            // the process the handle was created for.
+            let local_iov = iovec {
+                iov_base: buf.as_ptr() as *mut c_void,
+                iov_len: buf.len(),
+            };
+            let remote_iov = iovec {
+                iov_base: addr as *mut c_void,
+                iov_len: buf.len(),
+            };
+            let result = unsafe { process_vm_writev(self.pid, &local_iov, 1, &remote_iov, 1, 0) };
+            if result == -1 {
+                match io::Error::last_os_error().raw_os_error() {
+                    Some(libc::ENOSYS) | Some(libc::EPERM) => {
+                        // fallback to writing /proc/$pid/mem if kernel does not
+                        // implement process_vm_writev()
+                        self.write_proc_mem(addr, buf)
                     }
                     _ => Err(io::Error::last_os_error()),
                 }
+            } else if result as usize != buf.len() {
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "Mismatched write sizes for `process_vm_writev` (expected {}, got {})",
+                        buf.len(),
+                        result
+                    ),
+                ))
             } else {
                 Ok(())
             }
         }
     }
+
+    /// The maximum number of iovecs the kernel accepts in a single
+    /// `process_vm_readv` call (`UIO_MAXIOV`, exposed to userspace as
+    /// `IOV_MAX`).
+    const IOV_MAX: usize = 1024;
+
+    impl ProcessHandle {
+        /// Read as much of `buf` as possible from `addr` via `/proc/$pid/mem`,
+        /// opening it if this is the first time it is needed.
+        pub(crate) fn read_proc_mem(&self, addr: usize, buf: &mut [u8]) -> io::Result<usize> {
+            read_partial_at(&*self.proc_mem_file(false)?, addr as u64, buf)
+        }
+
+        /// Write all of `buf` to `addr` via `/proc/$pid/mem`, opening it for
+        /// writing if this is the first time it is needed.
+        fn write_proc_mem(&self, addr: usize, buf: &[u8]) -> io::Result<()> {
+            // Writes to the memory of an exited process just stop short, or
+            // fail to open the file, so report why up front.
+            self.check_alive()?;
+            self.proc_mem_file(true)?.write_all_at(buf, addr as u64)
+        }
+
+        /// Get the cached `/proc/$pid/mem`, opened for reading or `writable`,
+        /// opening it if needed. The lock is only held for that, so accesses
+        /// from several threads don't wait on each other; they don't share a
+        /// file position either, since they use `read_at` and `write_at`.
+        fn proc_mem_file(&self, writable: bool) -> io::Result<Arc<fs::File>> {
+            let cache = if writable {
+                &self.proc_mem_writable
+            } else {
+                &self.proc_mem
+            };
+            // Nothing can be left half done while holding the lock, so a
+            // poisoned lock is fine to use.
+            let mut proc_mem = cache
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some(ref file) = *proc_mem {
+                return Ok(file.clone());
+            }
+            let file = Arc::new(
+                fs::OpenOptions::new()
+                    .read(!writable)
+                    .write(writable)
+                    .open(format!("/proc/{}/mem", self.pid))?,
+            );
+            // If the process is still alive, the file we just opened is its.
+            self.check_alive()?;
+            *proc_mem = Some(file.clone());
+            Ok(file)
+        }
+
+        /// Gather up to `IOV_MAX` reads with a single `process_vm_readv`
+        /// call, returning how many of them, from the start, were read in
+        /// full and marking those as successful. Short of an error, the next
+        /// read is the one that failed.
+        fn readv_chunk(&self, reads: &mut [ReadOp<'_>]) -> io::Result<usize> {
+            let local_iov = reads
+                .iter_mut()
+                .map(|read| iovec {
+                    iov_base: read.buf.as_mut_ptr() as *mut c_void,
+                    iov_len: read.buf.len(),
+                })
+                .collect::<Vec<_>>();
+            let remote_iov = reads
+                .iter()
+                .map(|read| iovec {
+                    iov_base: read.addr as *mut c_void,
+                    iov_len: read.buf.len(),
+                })
+                .collect::<Vec<_>>();
+            let result = unsafe {
+                process_vm_readv(
+                    self.pid,
+                    local_iov.as_ptr(),
+                    local_iov.len() as _,
+                    remote_iov.as_ptr(),
+                    remote_iov.len() as _,
+                    0,
+                )
+            };
+            if result == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            self.check_alive()?;
+            // The kernel fills the iovecs in order and stops at the first
+            // unreadable byte.
+            let mut remaining = result as usize;
+            let mut done = 0;
+            for read in reads.iter_mut() {
+                if remaining < read.buf.len() {
+                    break;
+                }
+                remaining -= read.buf.len();
+                read.error = None;
+                done += 1;
+            }
+            Ok(done)
+        }
+    }
+
+    /// Read from `file` at `offset` until `buf` is full or a read fails,
+    /// returning the number of bytes read. Reads of `/proc/$pid/mem` stop with
+    /// `EIO` at the first unmapped page, so an error is only reported if
+    /// nothing was read.
+    fn read_partial_at(file: &fs::File, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let mut total = 0;
+        while total < buf.len() {
+            match file.read_at(&mut buf[total..], offset + total as u64) {
+                Ok(0) => break,
+                Ok(n) => total += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if total == 0 => return Err(e),
+                Err(_) => break,
+            }
+        }
+        if total == 0 {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to read any bytes from /proc/$pid/mem",
+            ))
+        } else {
+            Ok(total)
+        }
+    }
+
+    impl ProcessHandle {
+        /// List the memory mapped into the process by parsing
+        /// `/proc/$pid/maps`.
+        pub fn memory_regions(&self) -> io::Result<Vec<MemoryRegion>> {
+            let maps = fs::read(format!("/proc/{}/maps", self.pid))?;
+            // Otherwise these may be another process' mappings.
+            self.check_alive()?;
+            maps.split(|&b| b == b'\n')
+                .filter(|line| !line.is_empty())
+                .map(|line| {
+                    parse_maps_line(line).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "Malformed line in /proc/{}/maps: {:?}",
+                                self.pid,
+                                String::from_utf8_lossy(line)
+                            ),
+                        )
+                    })
+                })
+                .collect()
+        }
+    }
+
+    /// Parse a line of `/proc/$pid/maps`, which looks like:
+    ///
+    /// ```text
+    /// 7f0e0d5e2000-7f0e0d5e4000 r-xp 00002000 fd:01 1234    /usr/lib/libfoo.so
+    /// ```
+    fn parse_maps_line(line: &[u8]) -> Option<MemoryRegion> {
+        let mut fields = line.splitn(6, |&b| b == b' ');
+        let range = str::from_utf8(fields.next()?).ok()?;
+        let perms = fields.next()?;
+        // Skip the offset, device and inode.
+        fields.nth(2)?;
+        // The path is padded with spaces, and is missing for anonymous
+        // mappings. Pseudo-paths like `[heap]` aren't files.
+        let path = fields
+            .next()
+            .map(|path| {
+                let start = path.iter().position(|&b| b != b' ').unwrap_or(path.len());
+                &path[start..]
+            })
+            .filter(|path| !path.is_empty() && path[0] != b'[')
+            .map(|path| PathBuf::from(OsStr::from_bytes(path)));
+
+        let mut bounds = range.splitn(2, '-');
+        let start = usize::from_str_radix(bounds.next()?, 16).ok()?;
+        let end = usize::from_str_radix(bounds.next()?, 16).ok()?;
+        if perms.len() < 3 || end < start {
+            return None;
+        }
+        Some(MemoryRegion {
+            start,
+            size: end - start,
+            readable: perms[0] == b'r',
+            writable: perms[1] == b'w',
+            executable: perms[2] == b'x',
+            path,
+        })
+    }
 }
 
 #[cfg(target_os = "macos")]
 mod platform {
     use libc::{c_int, pid_t};
-    use mach::kern_return::{kern_return_t, KERN_SUCCESS};
-    use mach::port::{mach_port_name_t, mach_port_t, MACH_PORT_NULL};
+    use mach::kern_return::{kern_return_t, KERN_INVALID_ADDRESS, KERN_SUCCESS};
+    use mach::port::{
+        mach_port_name_t, mach_port_right_t, mach_port_t, MACH_PORT_NULL, MACH_PORT_RIGHT_SEND,
+    };
     use mach::vm_types::{mach_vm_address_t, mach_vm_size_t};
 
     use std::convert::TryFrom;
+    use std::ffi::OsStr;
     use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
     use std::process::Child;
 
-    use super::CopyAddress;
+    use super::{copy_address_chunked, CopyAddress, MemoryRegion, PutAddress};
 
     #[allow(non_camel_case_types)]
     type vm_map_t = mach_port_t;
@@ -145,13 +873,66 @@ mod platform {
     type vm_address_t = mach_vm_address_t;
     #[allow(non_camel_case_types)]
     type vm_size_t = mach_vm_size_t;
+    #[allow(non_camel_case_types)]
+    type vm_offset_t = libc::uintptr_t;
+    #[allow(non_camel_case_types)]
+    type mach_msg_type_number_t = libc::c_uint;
+
+    const VM_REGION_BASIC_INFO_64: c_int = 9;
+    const VM_PROT_READ: c_int = 0x1;
+    const VM_PROT_WRITE: c_int = 0x2;
+    const VM_PROT_EXECUTE: c_int = 0x4;
+
+    /// `struct vm_region_basic_info_64` from `<mach/vm_region.h>`, which is
+    /// declared with `#pragma pack(4)`.
+    #[repr(C, packed(4))]
+    #[derive(Default)]
+    struct VmRegionBasicInfo64 {
+        protection: c_int,
+        max_protection: c_int,
+        inheritance: libc::c_uint,
+        shared: libc::c_uint,
+        reserved: libc::c_uint,
+        offset: u64,
+        behavior: c_int,
+        user_wired_count: libc::c_ushort,
+    }
 
     /// On macOS a `Pid` is just a `libc::pid_t`.
     pub type Pid = pid_t;
-    /// On macOS a `ProcessHandle` is a mach port.
-    #[derive(Clone)]
+    /// On macOS a `ProcessHandle` is a send right to a task's mach port.
+    ///
+    /// The handle owns a reference to the send right: cloning it adds a
+    /// reference, and dropping it releases one.
     pub struct ProcessHandle(mach_port_name_t);
 
+    /// Add a reference to our send right `port`.
+    fn add_send_ref(port: mach_port_name_t) -> io::Result<()> {
+        let result = unsafe {
+            mach_port_mod_refs(mach::traps::mach_task_self(), port, MACH_PORT_RIGHT_SEND, 1)
+        };
+        if result != KERN_SUCCESS {
+            return Err(kern_error("mach_port_mod_refs", result));
+        }
+        Ok(())
+    }
+
+    /// Panics if no reference can be added, such as when the send right
+    /// already has the maximum number of references, rather than releasing
+    /// one too many later.
+    impl Clone for ProcessHandle {
+        fn clone(&self) -> Self {
+            add_send_ref(self.0).expect("failed to add a reference to the task port");
+            Self(self.0)
+        }
+    }
+
+    impl Drop for ProcessHandle {
+        fn drop(&mut self) {
+            unsafe { mach::mach_port::mach_port_deallocate(mach::traps::mach_task_self(), self.0) };
+        }
+    }
+
     extern "C" {
         fn vm_read_overwrite(
             target_task: vm_map_t,
@@ -160,13 +941,42 @@ mod platform {
             data: vm_address_t,
             out_size: *mut vm_size_t,
         ) -> kern_return_t;
+
+        fn mach_vm_region(
+            target_task: vm_map_t,
+            address: *mut mach_vm_address_t,
+            size: *mut mach_vm_size_t,
+            flavor: c_int,
+            info: *mut VmRegionBasicInfo64,
+            info_count: *mut mach_msg_type_number_t,
+            object_name: *mut mach_port_t,
+        ) -> kern_return_t;
+
+        fn pid_for_task(task: mach_port_name_t, pid: *mut c_int) -> kern_return_t;
+
+        fn mach_port_mod_refs(
+            task: mach_port_name_t,
+            name: mach_port_name_t,
+            right: mach_port_right_t,
+            delta: c_int,
+        ) -> kern_return_t;
+
+        fn mach_vm_write(
+            target_task: vm_map_t,
+            address: mach_vm_address_t,
+            data: vm_offset_t,
+            data_count: mach_msg_type_number_t,
+        ) -> kern_return_t;
     }
 
     /// A small wrapper around `task_for_pid`, which takes a pid and returns the
     /// mach port representing its task.
     fn task_for_pid(pid: Pid) -> io::Result<mach_port_name_t> {
         if pid == unsafe { libc::getpid() } as Pid {
-            return Ok(unsafe { mach::traps::mach_task_self() });
+            // Take a reference of our own, so the handle can release it.
+            let task = unsafe { mach::traps::mach_task_self() };
+            add_send_ref(task)?;
+            return Ok(task);
         }
 
         let mut task: mach_port_name_t = MACH_PORT_NULL;
@@ -191,11 +1001,26 @@ mod platform {
         }
     }
 
+    impl ProcessHandle {
+        /// Open `pid` for both reading and writing its memory.
+        ///
+        /// A task port can be used for both, so this is the same as
+        /// converting from a `Pid`. It exists for symmetry with Windows.
+        pub fn open_writable(pid: Pid) -> io::Result<Self> {
+            Self::try_from(pid)
+        }
+    }
+
     /// On Darwin, process handle is a mach port name.
+    ///
+    /// The handle adds a reference of its own to the send right named by
+    /// `mach_port_name`, and releases it when dropped, so the caller keeps
+    /// whatever references it already had.
     impl TryFrom<mach_port_name_t> for ProcessHandle {
         type Error = io::Error;
 
         fn try_from(mach_port_name: mach_port_name_t) -> io::Result<Self> {
+            add_send_ref(mach_port_name)?;
             Ok(Self(mach_port_name))
         }
     }
@@ -208,9 +1033,8 @@ mod platform {
     /// `for::Child`. This implementation is just provided for symmetry
     /// with other platforms to make writing cross-platform code easier.
     ///
-    /// Ideally we would provide an implementation of
-    /// `std::process::Command::spawn` that jumped through those hoops and
-    /// provided the task port.
+    /// `ProcessHandle::spawn` doesn't jump through them either, so it needs
+    /// the same privileges as this.
     impl TryFrom<&Child> for ProcessHandle {
         type Error = io::Error;
 
@@ -219,48 +1043,191 @@ mod platform {
         }
     }
 
+    /// The pid of the process `task` refers to, if it is still valid.
+    fn task_pid(task: mach_port_name_t) -> Option<Pid> {
+        let mut pid: c_int = 0;
+        if unsafe { pid_for_task(task, &mut pid) } == KERN_SUCCESS {
+            Some(pid as Pid)
+        } else {
+            None
+        }
+    }
+
+    /// Turn a failed `kern_return_t` into an `io::Error`.
+    fn kern_error(function: &str, result: kern_return_t) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("`{}` failed with kern_return_t {}", function, result),
+        )
+    }
+
+    /// Read all of `buf` from `addr` in `task` with `vm_read_overwrite`.
+    fn vm_read(task: mach_port_name_t, addr: usize, buf: &mut [u8]) -> io::Result<()> {
+        let mut read_len = buf.len() as vm_size_t;
+        let result = unsafe {
+            vm_read_overwrite(
+                task,
+                addr as vm_address_t,
+                buf.len() as vm_size_t,
+                buf.as_mut_ptr() as vm_address_t,
+                &mut read_len,
+            )
+        };
+
+        if read_len != buf.len() as vm_size_t {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "Mismatched read sizes for `vm_read` (expected {}, got {})",
+                    buf.len(),
+                    read_len
+                ),
+            ));
+        }
+
+        if result != KERN_SUCCESS {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
     /// Use `vm_read` to read memory from another process on macOS.
     impl CopyAddress for ProcessHandle {
         fn copy_address(&self, addr: usize, buf: &mut [u8]) -> io::Result<()> {
-            let mut read_len = buf.len() as vm_size_t;
+            if buf.is_empty() {
+                return Ok(());
+            }
+            vm_read(self.0, addr, buf)
+        }
+
+        /// `vm_read_overwrite` fails for the whole range if any page in it is
+        /// unreadable, so on failure we fall back to reading page by page.
+        fn copy_address_partial(&self, addr: usize, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            match vm_read(self.0, addr, buf) {
+                Ok(()) => Ok(buf.len()),
+                Err(_) => copy_address_chunked(|addr, buf| vm_read(self.0, addr, buf), addr, buf),
+            }
+        }
+    }
+
+    /// Use `mach_vm_write` to write memory to another process on macOS.
+    impl PutAddress for ProcessHandle {
+        fn put_address(&self, addr: usize, buf: &[u8]) -> io::Result<()> {
+            if buf.len() > mach_msg_type_number_t::MAX as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "buffer too large for a single `mach_vm_write`",
+                ));
+            }
+
             let result = unsafe {
-                vm_read_overwrite(
+                mach_vm_write(
                     self.0,
-                    addr as vm_address_t,
-                    buf.len() as vm_size_t,
-                    buf.as_mut_ptr() as vm_address_t,
-                    &mut read_len,
+                    addr as mach_vm_address_t,
+                    buf.as_ptr() as vm_offset_t,
+                    buf.len() as mach_msg_type_number_t,
                 )
             };
 
-            if read_len != buf.len() as vm_size_t {
+            if result != KERN_SUCCESS {
                 return Err(io::Error::new(
                     io::ErrorKind::Other,
-                    format!(
-                        "Mismatched read sizes for `vm_read` (expected {}, got {})",
-                        buf.len(),
-                        read_len
-                    ),
+                    format!("`mach_vm_write` failed with kern_return_t {}", result),
                 ));
             }
-
-            if result != KERN_SUCCESS {
-                return Err(io::Error::last_os_error());
-            }
             Ok(())
         }
     }
+
+    impl ProcessHandle {
+        /// List the memory mapped into the process with `mach_vm_region`.
+        pub fn memory_regions(&self) -> io::Result<Vec<MemoryRegion>> {
+            // `proc_regionfilename` wants a pid rather than a task port. Not
+            // being able to get one just means we can't report paths.
+            let pid = task_pid(self.0);
+
+            let mut regions = Vec::new();
+            let mut address: mach_vm_address_t = 0;
+            loop {
+                let mut size: mach_vm_size_t = 0;
+                let mut info = VmRegionBasicInfo64::default();
+                let mut info_count = (std::mem::size_of::<VmRegionBasicInfo64>()
+                    / std::mem::size_of::<c_int>())
+                    as mach_msg_type_number_t;
+                let mut object_name: mach_port_t = MACH_PORT_NULL;
+                let result = unsafe {
+                    mach_vm_region(
+                        self.0,
+                        &mut address,
+                        &mut size,
+                        VM_REGION_BASIC_INFO_64,
+                        &mut info,
+                        &mut info_count,
+                        &mut object_name,
+                    )
+                };
+                if result == KERN_INVALID_ADDRESS {
+                    // There are no more regions past `address`.
+                    break;
+                }
+                if result != KERN_SUCCESS {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("`mach_vm_region` failed with kern_return_t {}", result),
+                    ));
+                }
+
+                let protection = info.protection;
+                regions.push(MemoryRegion {
+                    start: address as usize,
+                    size: size as usize,
+                    readable: protection & VM_PROT_READ != 0,
+                    writable: protection & VM_PROT_WRITE != 0,
+                    executable: protection & VM_PROT_EXECUTE != 0,
+                    path: pid.and_then(|pid| region_filename(pid, address)),
+                });
+                address += size;
+            }
+            Ok(regions)
+        }
+    }
+
+    /// Look up the path of the file mapped at `address` in `pid`.
+    fn region_filename(pid: c_int, address: mach_vm_address_t) -> Option<PathBuf> {
+        let mut buf = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+        let len = unsafe {
+            libc::proc_regionfilename(
+                pid,
+                address,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len() as u32,
+            )
+        };
+        if len <= 0 {
+            return None;
+        }
+        buf.truncate(len as usize);
+        Some(PathBuf::from(OsStr::from_bytes(&buf)))
+    }
 }
 
 #[cfg(target_os = "freebsd")]
 mod platform {
-    use libc::{c_int, c_void, pid_t};
-    use libc::{waitpid, EBUSY, PIOD_READ_D, PT_ATTACH, PT_DETACH, PT_IO, WIFSTOPPED};
+    use libc::{c_char, c_int, c_void, pid_t};
+    use libc::{
+        waitpid, EBUSY, PIOD_READ_D, PIOD_WRITE_D, PT_ATTACH, PT_DETACH, PT_IO, WIFSTOPPED,
+    };
     use std::convert::TryFrom;
+    use std::ffi::{CStr, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
     use std::process::Child;
-    use std::{io, ptr};
+    use std::{io, ptr, slice};
 
-    use super::CopyAddress;
+    use super::{copy_address_chunked, CopyAddress, MemoryRegion, PutAddress};
 
     /// On FreeBSD a `Pid` is just a `libc::pid_t`.
     pub type Pid = pid_t;
@@ -290,7 +1257,7 @@ mod platform {
     extern "C" {
         /// libc version of ptrace takes *mut i8 as third argument,
         /// which is not very ergonomic if we have a struct.
-        fn ptrace(request: c_int, pid: pid_t, io_desc: *const PtraceIoDesc, data: c_int) -> c_int;
+        fn ptrace(request: c_int, pid: pid_t, io_desc: *mut PtraceIoDesc, data: c_int) -> c_int;
     }
 
     /// On FreeBSD, process handle is a pid.
@@ -302,6 +1269,17 @@ mod platform {
         }
     }
 
+    impl ProcessHandle {
+        /// Open `pid` for both reading and writing its memory.
+        ///
+        /// Handles need no special access rights to write here, so this is
+        /// the same as converting from a `Pid`. It exists for symmetry with
+        /// Windows.
+        pub fn open_writable(pid: Pid) -> io::Result<Self> {
+            Self::try_from(pid)
+        }
+    }
+
     /// A `process::Child` always has a pid, which is all we need on FreeBSD.
     impl TryFrom<&Child> for ProcessHandle {
         type Error = io::Error;
@@ -340,22 +1318,45 @@ mod platform {
         }
     }
 
-    /// Read process `pid` memory at `addr` to `buf` via PT_IO ptrace call.
-    fn ptrace_io(pid: Pid, addr: usize, buf: &mut [u8]) -> io::Result<()> {
-        let ptrace_io_desc = PtraceIoDesc {
-            piod_op: PIOD_READ_D,
+    /// Transfer `len` bytes between process `pid` memory at `addr` and
+    /// `local` via PT_IO ptrace call, returning the number of bytes actually
+    /// transferred. `op` is `PIOD_READ_D` or `PIOD_WRITE_D`.
+    fn ptrace_io(
+        pid: Pid,
+        op: c_int,
+        addr: usize,
+        local: *mut c_void,
+        len: usize,
+    ) -> io::Result<usize> {
+        let mut ptrace_io_desc = PtraceIoDesc {
+            piod_op: op,
             piod_offs: addr as *mut c_void,
-            piod_addr: buf.as_mut_ptr() as *mut c_void,
-            piod_len: buf.len(),
+            piod_addr: local,
+            piod_len: len,
         };
 
-        let result = unsafe { ptrace(PT_IO, pid, &ptrace_io_desc as *const _, 0) };
+        let result = unsafe { ptrace(PT_IO, pid, &mut ptrace_io_desc as *mut _, 0) };
 
         if result == -1 {
             Err(io::Error::last_os_error())
         } else {
-            Ok(())
+            Ok(ptrace_io_desc.piod_len)
+        }
+    }
+
+    /// Attach to `pid`, run `f` and detach again if we were the ones who
+    /// attached.
+    fn with_ptrace_attached<F, R>(pid: Pid, f: F) -> io::Result<R>
+    where
+        F: FnOnce() -> io::Result<R>,
+    {
+        let should_detach = ptrace_attach(pid)? == PtraceLockState::Release;
+
+        let result = f();
+        if should_detach {
+            ptrace_detach(pid)?
         }
+        result
     }
 
     /// Detach from the process `pid`.
@@ -369,15 +1370,111 @@ mod platform {
         }
     }
 
+    /// Read all of `buf` from `addr` in `pid` with a single `PT_IO` call,
+    /// which must already be attached.
+    fn ptrace_read(pid: Pid, addr: usize, buf: &mut [u8]) -> io::Result<()> {
+        let read = ptrace_io(
+            pid,
+            PIOD_READ_D,
+            addr,
+            buf.as_mut_ptr() as *mut c_void,
+            buf.len(),
+        )?;
+        if read != buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Mismatched read sizes for `PT_IO` (expected {}, got {})",
+                    buf.len(),
+                    read
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     impl CopyAddress for ProcessHandle {
         fn copy_address(&self, addr: usize, buf: &mut [u8]) -> io::Result<()> {
-            let should_detach = ptrace_attach(self.0)? == PtraceLockState::Release;
+            if buf.is_empty() {
+                return Ok(());
+            }
+            with_ptrace_attached(self.0, || ptrace_read(self.0, addr, buf))
+        }
+
+        /// `PT_IO` fails with `EFAULT` at the first unmapped page without
+        /// telling us how much it copied before that, so on failure we fall
+        /// back to reading page by page.
+        fn copy_address_partial(&self, addr: usize, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            with_ptrace_attached(self.0, || match ptrace_read(self.0, addr, buf) {
+                Ok(()) => Ok(buf.len()),
+                Err(_) => {
+                    copy_address_chunked(|addr, buf| ptrace_read(self.0, addr, buf), addr, buf)
+                }
+            })
+        }
+    }
 
-            let result = ptrace_io(self.0, addr, buf);
-            if should_detach {
-                ptrace_detach(self.0)?
+    impl PutAddress for ProcessHandle {
+        fn put_address(&self, addr: usize, buf: &[u8]) -> io::Result<()> {
+            let written = with_ptrace_attached(self.0, || {
+                ptrace_io(
+                    self.0,
+                    PIOD_WRITE_D,
+                    addr,
+                    buf.as_ptr() as *mut c_void,
+                    buf.len(),
+                )
+            })?;
+            if written != buf.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "Mismatched write sizes for `PT_IO` (expected {}, got {})",
+                        buf.len(),
+                        written
+                    ),
+                ));
             }
-            result
+            Ok(())
+        }
+    }
+
+    impl ProcessHandle {
+        /// List the memory mapped into the process with `kinfo_getvmmap`.
+        ///
+        /// Unlike reading memory, this doesn't need to attach to (and so
+        /// stop) the process.
+        pub fn memory_regions(&self) -> io::Result<Vec<MemoryRegion>> {
+            let mut count: c_int = 0;
+            let entries = unsafe { libc::kinfo_getvmmap(self.0, &mut count) };
+            if entries.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+
+            let regions = unsafe { slice::from_raw_parts(entries, count as usize) }
+                .iter()
+                .map(|entry| {
+                    let path = unsafe { CStr::from_ptr(entry.kve_path.as_ptr() as *const c_char) };
+                    let prot = entry.kve_protection;
+                    MemoryRegion {
+                        start: entry.kve_start as usize,
+                        size: (entry.kve_end - entry.kve_start) as usize,
+                        readable: prot & libc::KVME_PROT_READ != 0,
+                        writable: prot & libc::KVME_PROT_WRITE != 0,
+                        executable: prot & libc::KVME_PROT_EXEC != 0,
+                        path: if path.to_bytes().is_empty() {
+                            None
+                        } else {
+                            Some(PathBuf::from(OsStr::from_bytes(path.to_bytes())))
+                        },
+                    }
+                })
+                .collect();
+            unsafe { libc::free(entries as *mut c_void) };
+            Ok(regions)
         }
     }
 }
@@ -385,19 +1482,22 @@ mod platform {
 #[cfg(windows)]
 mod platform {
     use std::convert::TryFrom;
+    use std::ffi::OsString;
     use std::io;
     use std::mem;
     use std::ops::Deref;
-    use std::os::windows::io::{AsRawHandle, RawHandle};
+    use std::os::windows::ffi::OsStringExt;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
+    use std::path::PathBuf;
     use std::process::Child;
     use std::ptr;
     use std::sync::Arc;
     use winapi::{
-        shared::{basetsd, minwindef},
-        um::{handleapi, memoryapi, processthreadsapi, winnt},
+        shared::{basetsd, minwindef, winerror},
+        um::{handleapi, memoryapi, processthreadsapi, psapi, winnt},
     };
 
-    use super::CopyAddress;
+    use super::{copy_address_chunked, CopyAddress, MemoryRegion, PutAddress};
 
     /// On Windows a `Pid` is a `DWORD`.
     pub type Pid = minwindef::DWORD;
@@ -421,58 +1521,349 @@ mod platform {
         }
     }
 
-    /// A `Pid` can be turned into a `ProcessHandle` with `OpenProcess`.
-    impl TryFrom<Pid> for ProcessHandle {
-        type Error = io::Error;
-
-        fn try_from(pid: Pid) -> io::Result<Self> {
-            let handle = unsafe { processthreadsapi::OpenProcess(winnt::PROCESS_VM_READ, 0, pid) };
+    // Process handles may be used and closed from any thread.
+    unsafe impl Send for ProcessHandleInner {}
+    unsafe impl Sync for ProcessHandleInner {}
+
+    /// The access rights requested by `TryFrom<Pid>` and `TryFrom<&Child>`:
+    /// enough to read memory, list memory regions and query the exit status.
+    const DEFAULT_ACCESS: minwindef::DWORD = winnt::PROCESS_VM_READ
+        | winnt::PROCESS_QUERY_INFORMATION
+        | winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    impl ProcessHandle {
+        /// Open `pid` with `OpenProcess`, requesting exactly the `access`
+        /// rights, which are a combination of `winnt::PROCESS_*` flags.
+        ///
+        /// The rights needed by the rest of this crate are:
+        ///
+        /// - `PROCESS_VM_READ` for `CopyAddress`.
+        /// - `PROCESS_VM_WRITE | PROCESS_VM_OPERATION` for `PutAddress`.
+        /// - `PROCESS_QUERY_INFORMATION` for `ProcessHandle::memory_regions`.
+        /// - `PROCESS_QUERY_LIMITED_INFORMATION` for querying the exit status
+        ///   with `GetExitCodeProcess` through `AsRawHandle`.
+        ///
+        /// `TryFrom<Pid>` and `TryFrom<&Child>` ask for all of these except
+        /// the ones for `PutAddress`, so their handles can't write.
+        ///
+        /// Asking for fewer rights may allow opening processes that are
+        /// otherwise protected, at the cost of the corresponding methods
+        /// failing with an access denied error.
+        pub fn open_with_access(pid: Pid, access: minwindef::DWORD) -> io::Result<Self> {
+            let handle = unsafe { processthreadsapi::OpenProcess(access, 0, pid) };
             if handle == (0 as RawHandle) {
                 Err(io::Error::last_os_error())
             } else {
                 Ok(Self(Arc::new(ProcessHandleInner(handle))))
             }
         }
+
+        /// Open `pid` for both reading and writing its memory.
+        ///
+        /// On Windows this asks `OpenProcess` for `PROCESS_VM_WRITE` and
+        /// `PROCESS_VM_OPERATION` in addition to the rights `TryFrom<Pid>`
+        /// asks for, which `PutAddress` needs. This is not the default since
+        /// it may fail for processes that could otherwise be read.
+        pub fn open_writable(pid: Pid) -> io::Result<Self> {
+            Self::open_with_access(
+                pid,
+                DEFAULT_ACCESS | winnt::PROCESS_VM_WRITE | winnt::PROCESS_VM_OPERATION,
+            )
+        }
+    }
+
+    /// A `Pid` can be turned into a `ProcessHandle` with `OpenProcess`.
+    ///
+    /// The handle is opened with `PROCESS_VM_READ`,
+    /// `PROCESS_QUERY_INFORMATION` and `PROCESS_QUERY_LIMITED_INFORMATION`
+    /// access, for reading memory, listing memory regions and querying the
+    /// exit status. Use `ProcessHandle::open_writable` to be able to write
+    /// to it as well, or `ProcessHandle::open_with_access` to pick the rights
+    /// yourself.
+    impl TryFrom<Pid> for ProcessHandle {
+        type Error = io::Error;
+
+        fn try_from(pid: Pid) -> io::Result<Self> {
+            Self::open_with_access(pid, DEFAULT_ACCESS)
+        }
     }
 
     /// A `std::process::Child` has a `HANDLE` from calling `CreateProcess`.
+    ///
+    /// The `Child` keeps ownership of that handle, so it is duplicated with
+    /// `DuplicateHandle`, asking for the same rights as `TryFrom<Pid>`, so the
+    /// duplicate can't be used to write. The `Child` and the
+    /// `ProcessHandle` can then be dropped in any order.
     impl TryFrom<&Child> for ProcessHandle {
         type Error = io::Error;
 
         fn try_from(child: &Child) -> io::Result<Self> {
-            Ok(Self(Arc::new(ProcessHandleInner(child.as_raw_handle()))))
+            let mut handle: RawHandle = ptr::null_mut();
+            if unsafe {
+                let current = processthreadsapi::GetCurrentProcess();
+                handleapi::DuplicateHandle(
+                    current,
+                    child.as_raw_handle(),
+                    current,
+                    &mut handle,
+                    DEFAULT_ACCESS,
+                    0,
+                    0,
+                )
+            } == 0
+            {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(Self(Arc::new(ProcessHandleInner(handle))))
+            }
         }
     }
 
-    impl From<RawHandle> for ProcessHandle {
-        fn from(handle: RawHandle) -> Self {
-            return Self(Arc::new(ProcessHandleInner(handle)));
+    /// Take ownership of a raw process `HANDLE`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid process handle which is not closed by anything
+    /// else: the `ProcessHandle` closes it once it and all its clones are
+    /// dropped. Duplicate the handle first with `DuplicateHandle` if it is
+    /// still owned elsewhere.
+    impl FromRawHandle for ProcessHandle {
+        unsafe fn from_raw_handle(handle: RawHandle) -> Self {
+            Self(Arc::new(ProcessHandleInner(handle)))
+        }
+    }
+
+    /// The returned handle is still owned by the `ProcessHandle`, and must
+    /// not be closed.
+    impl AsRawHandle for ProcessHandle {
+        fn as_raw_handle(&self) -> RawHandle {
+            self.0 .0
+        }
+    }
+
+    /// Call `ReadProcessMemory`, returning how many bytes it reported
+    /// reading along with its error, if any.
+    fn read_process_memory(
+        handle: RawHandle,
+        addr: usize,
+        buf: &mut [u8],
+    ) -> (usize, Option<io::Error>) {
+        let mut read: basetsd::SIZE_T = 0;
+        if unsafe {
+            memoryapi::ReadProcessMemory(
+                handle,
+                addr as minwindef::LPVOID,
+                buf.as_mut_ptr() as minwindef::LPVOID,
+                mem::size_of_val(buf) as basetsd::SIZE_T,
+                &mut read,
+            )
+        } == 0
+        {
+            (read as usize, Some(io::Error::last_os_error()))
+        } else {
+            (read as usize, None)
         }
     }
 
     /// Use `ReadProcessMemory` to read memory from another process on Windows.
     impl CopyAddress for ProcessHandle {
         fn copy_address(&self, addr: usize, buf: &mut [u8]) -> io::Result<()> {
-            if buf.len() == 0 {
+            if buf.is_empty() {
                 return Ok(());
             }
+            match read_process_memory(self.0 .0, addr, buf) {
+                (_, Some(error)) => Err(error),
+                (read, None) if read != buf.len() => Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "Mismatched read sizes for `ReadProcessMemory` (expected {}, got {})",
+                        buf.len(),
+                        read
+                    ),
+                )),
+                (_, None) => Ok(()),
+            }
+        }
+
+        fn copy_address_partial(&self, addr: usize, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            let error = match read_process_memory(self.0 .0, addr, buf) {
+                (read, None) => return Ok(read),
+                (read, Some(error)) => match error.raw_os_error() {
+                    Some(code) if code == winerror::ERROR_PARTIAL_COPY as i32 => {
+                        if read > 0 {
+                            return Ok(read);
+                        }
+                        error
+                    }
+                    _ => return Err(error),
+                },
+            };
 
+            // `ReadProcessMemory` may or may not report how much it managed to
+            // copy before hitting an inaccessible page, so find out ourselves
+            // if it didn't.
+            copy_address_chunked(
+                |addr, buf| match read_process_memory(self.0 .0, addr, buf) {
+                    (read, None) if read == buf.len() => Ok(()),
+                    (_, Some(error)) => Err(error),
+                    (_, None) => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+                },
+                addr,
+                buf,
+            )
+            .map_err(|_| error)
+        }
+    }
+
+    /// Use `WriteProcessMemory` to write memory to another process on Windows.
+    ///
+    /// This requires a handle opened with `PROCESS_VM_WRITE` and
+    /// `PROCESS_VM_OPERATION` access, such as one from
+    /// `ProcessHandle::open_writable`. Handles converted from a `Pid` or a
+    /// `Child` do not have those.
+    impl PutAddress for ProcessHandle {
+        fn put_address(&self, addr: usize, buf: &[u8]) -> io::Result<()> {
+            if buf.is_empty() {
+                return Ok(());
+            }
+
+            let mut written: basetsd::SIZE_T = 0;
             if unsafe {
-                memoryapi::ReadProcessMemory(
+                memoryapi::WriteProcessMemory(
                     self.0 .0,
                     addr as minwindef::LPVOID,
-                    buf.as_mut_ptr() as minwindef::LPVOID,
+                    buf.as_ptr() as minwindef::LPCVOID,
                     mem::size_of_val(buf) as basetsd::SIZE_T,
-                    ptr::null_mut(),
+                    &mut written,
                 )
             } == 0
             {
-                Err(io::Error::last_os_error())
-            } else {
-                Ok(())
+                let error = io::Error::last_os_error();
+                return match error.raw_os_error() {
+                    Some(code) if code == winerror::ERROR_ACCESS_DENIED as i32 => {
+                        Err(io::Error::new(
+                            io::ErrorKind::PermissionDenied,
+                            format!(
+                                "`WriteProcessMemory` was denied access ({}); the process handle \
+                                 needs `PROCESS_VM_WRITE | PROCESS_VM_OPERATION` access rights",
+                                error
+                            ),
+                        ))
+                    }
+                    _ => Err(error),
+                };
+            }
+
+            if written as usize != buf.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "Mismatched write sizes for `WriteProcessMemory` (expected {}, got {})",
+                        buf.len(),
+                        written
+                    ),
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    impl ProcessHandle {
+        /// List the committed memory of the process with `VirtualQueryEx`,
+        /// skipping guard pages.
+        ///
+        /// This requires a handle with `PROCESS_QUERY_INFORMATION` access.
+        pub fn memory_regions(&self) -> io::Result<Vec<MemoryRegion>> {
+            let mut regions = Vec::new();
+            let mut address: usize = 0;
+            loop {
+                let mut info: winnt::MEMORY_BASIC_INFORMATION = unsafe { mem::zeroed() };
+                let len = unsafe {
+                    memoryapi::VirtualQueryEx(
+                        self.0 .0,
+                        address as minwindef::LPCVOID,
+                        &mut info,
+                        mem::size_of_val(&info) as basetsd::SIZE_T,
+                    )
+                };
+                if len == 0 {
+                    let error = io::Error::last_os_error();
+                    // Querying past the end of the address space fails with
+                    // `ERROR_INVALID_PARAMETER`.
+                    if error.raw_os_error() == Some(winerror::ERROR_INVALID_PARAMETER as i32)
+                        && !regions.is_empty()
+                    {
+                        break;
+                    }
+                    return Err(error);
+                }
+
+                let start = info.BaseAddress as usize;
+                let size = info.RegionSize as usize;
+                // Guard pages raise an exception when touched, so they are no
+                // more readable than reserved memory.
+                if info.State == winnt::MEM_COMMIT && info.Protect & winnt::PAGE_GUARD == 0 {
+                    let (readable, writable, executable) = protection_flags(info.Protect);
+                    let path = if info.Type == winnt::MEM_IMAGE || info.Type == winnt::MEM_MAPPED {
+                        mapped_file_name(self.0 .0, start)
+                    } else {
+                        None
+                    };
+                    regions.push(MemoryRegion {
+                        start,
+                        size,
+                        readable,
+                        writable,
+                        executable,
+                        path,
+                    });
+                }
+
+                match start.checked_add(size) {
+                    Some(next) if next > address => address = next,
+                    _ => break,
+                }
             }
+            Ok(regions)
         }
     }
+
+    /// Decode `PAGE_*` protection constants into whether the memory is
+    /// readable, writable and executable.
+    fn protection_flags(protect: minwindef::DWORD) -> (bool, bool, bool) {
+        // The low byte holds the access; the rest are modifiers such as
+        // `PAGE_NOCACHE`.
+        match protect & 0xff {
+            winnt::PAGE_READONLY => (true, false, false),
+            winnt::PAGE_READWRITE | winnt::PAGE_WRITECOPY => (true, true, false),
+            winnt::PAGE_EXECUTE => (false, false, true),
+            winnt::PAGE_EXECUTE_READ => (true, false, true),
+            winnt::PAGE_EXECUTE_READWRITE | winnt::PAGE_EXECUTE_WRITECOPY => (true, true, true),
+            _ => (false, false, false),
+        }
+    }
+
+    /// Look up the path of the file mapped at `address` in `handle`.
+    fn mapped_file_name(handle: RawHandle, address: usize) -> Option<PathBuf> {
+        let mut buf = vec![0u16; 1024];
+        let len = unsafe {
+            psapi::GetMappedFileNameW(
+                handle,
+                address as minwindef::LPVOID,
+                buf.as_mut_ptr(),
+                buf.len() as minwindef::DWORD,
+            )
+        };
+        if len == 0 {
+            return None;
+        }
+        buf.truncate(len as usize);
+        Some(PathBuf::from(OsString::from_wide(&buf)))
+    }
 }
 
 /// Copy `length` bytes of memory at `addr` from `source`.
@@ -496,12 +1887,307 @@ where
         .and(Ok(copy))
 }
 
+/// Copy up to `length` bytes of memory at `addr` from `source`.
+///
+/// This is like `copy_address`, but calls `CopyAddress::copy_address_partial`
+/// and truncates the returned `Vec` to the bytes that could actually be read
+/// instead of failing when only part of the range is readable.
+pub fn copy_address_lossy<T>(addr: usize, length: usize, source: &T) -> io::Result<Vec<u8>>
+where
+    T: CopyAddress,
+{
+    log::debug!("copy_address_lossy: addr: {:x}", addr);
+
+    let mut copy = vec![0; length];
+
+    source
+        .copy_address_partial(addr, &mut copy)
+        .map_err(|e| {
+            log::warn!("copy_address_lossy failed for {:x}: {:?}", addr, e);
+            e
+        })
+        .map(|read| {
+            copy.truncate(read);
+            copy
+        })
+}
+
+/// Types that can be safely created from any bit pattern of the right size,
+/// for reading with `copy_value` and `copy_array`.
+///
+/// Values are read in the target's native layout and byte order, which is
+/// only the same as ours if it runs on the same architecture.
+///
+/// # Safety
+///
+/// Every bit pattern of `size_of::<Self>()` bytes must be a valid value of
+/// the type. This rules out types such as `bool`, `char`, enums, references
+/// and anything containing them. A `#[repr(C)]` struct whose fields are all
+/// `Pod` qualifies, as long as nothing relies on the contents of its
+/// padding.
+pub unsafe trait Pod: Copy {}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(unsafe impl Pod for $t {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+unsafe impl<T> Pod for *const T {}
+unsafe impl<T> Pod for *mut T {}
+
+/// Copy a `T` from memory at `addr` in `source`.
+///
+/// This reads exactly `size_of::<T>()` bytes with `copy_address`, so `addr`
+/// doesn't need to be aligned.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io;
+/// use read_process_memory::*;
+///
+/// # fn foo(handle: &ProcessHandle, address: usize) -> io::Result<()> {
+/// let value: u64 = copy_value(address, handle)?;
+/// let pointers: Vec<*const u8> = copy_array(address, 4, handle)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn copy_value<T, S>(addr: usize, source: &S) -> io::Result<T>
+where
+    T: Pod,
+    S: CopyAddress,
+{
+    let bytes = copy_address(addr, std::mem::size_of::<T>(), source)?;
+    // `Pod` guarantees that any bytes make a valid `T`.
+    Ok(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+/// Copy `count` consecutive `T`s from memory at `addr` in `source`.
+///
+/// This is like `copy_value`, but reads a whole array with a single call to
+/// `copy_address`.
+pub fn copy_array<T, S>(addr: usize, count: usize, source: &S) -> io::Result<Vec<T>>
+where
+    T: Pod,
+    S: CopyAddress,
+{
+    let size = std::mem::size_of::<T>();
+    let length = size.checked_mul(count).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} values of {} bytes overflow usize", count, size),
+        )
+    })?;
+    let bytes = copy_address(addr, length, source)?;
+    // `Pod` guarantees that any bytes make a valid `T`.
+    Ok((0..count)
+        .map(|i| unsafe { std::ptr::read_unaligned(bytes.as_ptr().add(i * size) as *const T) })
+        .collect())
+}
+
+/// Write `data` to memory at `addr` in `dest`.
+///
+/// This is the writing counterpart of `copy_address`, and is just a
+/// convenient way to call `PutAddress::put_address` with the same logging.
+pub fn put_address<T>(addr: usize, data: &[u8], dest: &T) -> io::Result<()>
+where
+    T: PutAddress,
+{
+    log::debug!("put_address: addr: {:x}", addr);
+
+    dest.put_address(addr, data).map_err(|e| {
+        log::warn!("put_address failed for {:x}: {:?}", addr, e);
+        e
+    })
+}
+
+/// Write `data` to memory at `addr` in `dest`.
+///
+/// This is the same as `put_address`, under its original name.
+pub fn write_address<T>(addr: usize, data: &[u8], dest: &T) -> io::Result<()>
+where
+    T: PutAddress,
+{
+    put_address(addr, data, dest)
+}
+
+/// Spawn `cmd` and get a `ProcessHandle` to the new process.
+///
+/// This is just a convenient way to call `ProcessHandle::spawn` with the
+/// same logging as the other helpers.
+pub fn spawn_with_handle(
+    cmd: &mut std::process::Command,
+) -> io::Result<(std::process::Child, ProcessHandle)> {
+    ProcessHandle::spawn(cmd).map_err(|e| {
+        log::warn!("spawn_with_handle failed: {:?}", e);
+        e
+    })
+}
+
+/// An adapter implementing `io::Read` and `io::Seek` on top of
+/// `CopyAddress`, for use with code that parses data from a reader.
+///
+/// Positions are offsets from the base address the reader was created with,
+/// so seeking to `SeekFrom::Start(0)` goes back to the base address. Reads are
+/// passed straight to `CopyAddress::copy_address_partial`, so wrap this in an
+/// `io::BufReader` when doing many small reads.
+///
+/// A read that runs into unreadable memory returns however many bytes could
+/// be read before it. A read starting at unreadable memory fails with the
+/// error from `copy_address_partial`, since there is no way to tell the end
+/// of a mapping from an unmapped hole. For loops like `read_to_end` to simply
+/// stop, give the reader a length with `ProcessMemoryReader::with_len`, past
+/// which it reports end of file.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io;
+/// use read_process_memory::*;
+/// use std::io::{BufReader, Read};
+///
+/// # fn foo(handle: ProcessHandle, address: usize) -> io::Result<()> {
+/// let mut reader = BufReader::new(ProcessMemoryReader::new(handle, address));
+/// let mut magic = [0u8; 4];
+/// reader.read_exact(&mut magic)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProcessMemoryReader<T> {
+    source: T,
+    base: usize,
+    len: Option<u64>,
+    pos: u64,
+}
+
+impl<T> ProcessMemoryReader<T>
+where
+    T: CopyAddress,
+{
+    /// Create a reader for the memory of `source` starting at `base`, with
+    /// no end.
+    ///
+    /// Seeking relative to `SeekFrom::End` fails with `InvalidInput`.
+    pub fn new(source: T, base: usize) -> Self {
+        ProcessMemoryReader {
+            source,
+            base,
+            len: None,
+            pos: 0,
+        }
+    }
+
+    /// Create a reader for the `len` bytes of memory of `source` starting at
+    /// `base`.
+    pub fn with_len(source: T, base: usize, len: usize) -> Self {
+        ProcessMemoryReader {
+            len: Some(len as u64),
+            ..Self::new(source, base)
+        }
+    }
+
+    /// The address the reader's positions are relative to.
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// The current position of the reader, relative to the base address.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Get a reference to the underlying `CopyAddress`.
+    pub fn get_ref(&self) -> &T {
+        &self.source
+    }
+
+    /// Unwrap this reader, returning the underlying `CopyAddress`.
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+}
+
+impl<T> io::Read for ProcessMemoryReader<T>
+where
+    T: CopyAddress,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let buf = match self.len {
+            Some(len) => {
+                let remaining = len.saturating_sub(self.pos);
+                let end = buf.len().min(remaining.min(usize::MAX as u64) as usize);
+                &mut buf[..end]
+            }
+            None => buf,
+        };
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let addr = usize::try_from(self.pos)
+            .ok()
+            .and_then(|pos| self.base.checked_add(pos))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "position is past the end of the address space",
+                )
+            })?;
+        let read = self.source.copy_address_partial(addr, buf)?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl<T> io::Seek for ProcessMemoryReader<T>
+where
+    T: CopyAddress,
+{
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (origin, offset) = match pos {
+            io::SeekFrom::Start(pos) => {
+                self.pos = pos;
+                return Ok(pos);
+            }
+            io::SeekFrom::Current(offset) => (self.pos, offset),
+            io::SeekFrom::End(offset) => match self.len {
+                Some(len) => (len, offset),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "cannot seek relative to the end of a reader without a length",
+                    ))
+                }
+            },
+        };
+        let pos = if offset >= 0 {
+            origin.checked_add(offset as u64)
+        } else {
+            origin.checked_sub(offset.wrapping_neg() as u64)
+        };
+        match pos {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::convert::TryFrom;
     use std::env;
-    use std::io::{self, BufRead, BufReader};
+    use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
     use std::path::PathBuf;
     use std::process::{Child, Command, Stdio};
 
@@ -514,14 +2200,11 @@ mod test {
         })
     }
 
-    fn spawn_with_handle(cmd: &mut Command) -> io::Result<(Child, ProcessHandle)> {
-        let child = cmd.spawn()?;
-        let handle = ProcessHandle::try_from(child.id() as Pid)?;
-        Ok((child, handle))
-    }
-
-    fn read_test_process(args: Option<&[&str]>) -> io::Result<Vec<u8>> {
-        // Spawn a child process and attempt to read its memory.
+    /// Spawn the test child and return it along with a handle to it and the
+    /// address and size of the data it allocated.
+    fn spawn_test_process(
+        args: Option<&[&str]>,
+    ) -> io::Result<(Child, ProcessHandle, usize, usize)> {
         let path = test_process_path().unwrap();
         let mut cmd = Command::new(&path);
         {
@@ -530,7 +2213,8 @@ mod test {
         if let Some(a) = args {
             cmd.args(a);
         }
-        let (mut child, handle) = spawn_with_handle(&mut cmd)?;
+        let mut child = cmd.spawn()?;
+        let handle = ProcessHandle::try_from(&child)?;
         // The test program prints the address and size.
         // See `src/bin/test.rs` for its source.
         let reader = BufReader::new(child.stdout.take().unwrap());
@@ -538,11 +2222,23 @@ mod test {
         let bits = line.split(' ').collect::<Vec<_>>();
         let addr = usize::from_str_radix(&bits[0][2..], 16).unwrap();
         let size = bits[1].parse::<usize>().unwrap();
+        Ok((child, handle, addr, size))
+    }
+
+    fn read_test_process(args: Option<&[&str]>) -> io::Result<Vec<u8>> {
+        // Spawn a child process and attempt to read its memory.
+        let (mut child, handle, addr, size) = spawn_test_process(args)?;
         let mem = copy_address(addr, size, &handle)?;
         child.wait()?;
         Ok(mem)
     }
 
+    fn expected_pattern(size: usize) -> Vec<u8> {
+        (0..size)
+            .map(|v| (v % (u8::MAX as usize + 1)) as u8)
+            .collect::<Vec<u8>>()
+    }
+
     #[test]
     fn test_read_small() {
         let mem = read_test_process(None).unwrap();
@@ -556,9 +2252,454 @@ mod test {
         const SIZE: usize = 20_000;
         let arg = format!("{}", SIZE);
         let mem = read_test_process(Some(&[&arg])).unwrap();
-        let expected = (0..SIZE)
-            .map(|v| (v % (u8::max_value() as usize + 1)) as u8)
-            .collect::<Vec<u8>>();
-        assert_eq!(mem, expected);
+        assert_eq!(mem, expected_pattern(SIZE));
+    }
+
+    #[test]
+    fn test_read_partial_end_of_mapping() {
+        const SIZE: usize = 1000;
+        let arg = format!("{}", SIZE);
+        let (mut child, handle, addr, size) =
+            spawn_test_process(Some(&[&arg, "end-of-mapping"])).unwrap();
+        assert_eq!(size, SIZE);
+
+        // Reading exactly up to the end of the mapping works either way.
+        let mut buf = vec![0; SIZE];
+        assert_eq!(handle.copy_address_partial(addr, &mut buf).unwrap(), SIZE);
+        assert_eq!(buf, expected_pattern(SIZE));
+
+        // Reading past the end only succeeds for the partial read.
+        let mut buf = vec![0; SIZE + 100];
+        assert!(handle.copy_address(addr, &mut buf).is_err());
+        assert_eq!(handle.copy_address_partial(addr, &mut buf).unwrap(), SIZE);
+        assert_eq!(&buf[..SIZE], &expected_pattern(SIZE)[..]);
+
+        let mem = copy_address_lossy(addr + 10, SIZE, &handle).unwrap();
+        assert_eq!(mem, &expected_pattern(SIZE)[10..]);
+
+        // Nothing at all is readable past the end of the mapping.
+        let mut buf = vec![0; 100];
+        assert!(handle.copy_address_partial(addr + SIZE, &mut buf).is_err());
+        assert!(copy_address_lossy(addr + SIZE, 100, &handle).is_err());
+
+        // Ranges running off the end of the address space fail cleanly.
+        let mut buf = vec![0; 100];
+        assert!(handle
+            .copy_address_partial(usize::MAX - 3, &mut buf)
+            .is_err());
+
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_spawn_with_handle() {
+        let mut cmd = Command::new(test_process_path().unwrap());
+        cmd.stdin(Stdio::piped()).stdout(Stdio::piped());
+        let (mut child, handle) = spawn_with_handle(&mut cmd).unwrap();
+        let reader = BufReader::new(child.stdout.take().unwrap());
+        let line = reader.lines().next().unwrap().unwrap();
+        let addr = usize::from_str_radix(&line.split(' ').next().unwrap()[2..], 16).unwrap();
+        assert_eq!(
+            copy_address(addr, 32, &handle).unwrap(),
+            expected_pattern(32)
+        );
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_copy_value() {
+        let (mut child, handle, addr, size) = spawn_test_process(None).unwrap();
+        let expected = expected_pattern(size);
+
+        // Reads don't need to be aligned.
+        let value: u32 = copy_value(addr + 1, &handle).unwrap();
+        assert_eq!(value, u32::from_ne_bytes([1, 2, 3, 4]));
+        let array: [u8; 8] = copy_value(addr, &handle).unwrap();
+        assert_eq!(array, &expected[..8]);
+
+        let values: Vec<u16> = copy_array(addr + 3, 4, &handle).unwrap();
+        let expected_values = expected[3..11]
+            .chunks(2)
+            .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(values, expected_values);
+        assert!(copy_array::<u8, _>(addr, 0, &handle).unwrap().is_empty());
+
+        assert!(copy_value::<u64, _>(0, &handle).is_err());
+        assert_eq!(
+            copy_array::<u64, _>(addr, usize::MAX, &handle)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_read_batch() {
+        const SIZE: usize = 20_000;
+        let arg = format!("{}", SIZE);
+        let (mut child, handle, addr, _) = spawn_test_process(Some(&[&arg])).unwrap();
+        let expected = expected_pattern(SIZE);
+
+        // Enough scattered requests to need more than one `process_vm_readv`
+        // call on Linux.
+        let offsets = (0..SIZE / 8).map(|i| i * 8).rev().collect::<Vec<_>>();
+        let mut bufs = vec![[0u8; 4]; offsets.len()];
+        let mut requests = offsets
+            .iter()
+            .zip(bufs.iter_mut())
+            .map(|(offset, buf)| (addr + offset, &mut buf[..]))
+            .collect::<Vec<_>>();
+        handle.copy_address_batch(&mut requests).unwrap();
+        for (offset, buf) in offsets.iter().zip(bufs.iter()) {
+            assert_eq!(&buf[..], &expected[*offset..*offset + 4]);
+        }
+
+        // Failing requests are reported individually.
+        let mut a = [0u8; 16];
+        let mut b = [0u8; 16];
+        let mut c = [0u8; 16];
+        let mut d = [0u8; 16];
+        let mut requests = [
+            (addr, &mut a[..]),
+            (0x10, &mut b[..]),
+            (addr + 100, &mut c[..]),
+            (0x20, &mut d[..]),
+        ];
+        let e = handle.copy_address_batch(&mut requests).unwrap_err();
+        let batch = e.get_ref().unwrap().downcast_ref::<BatchError>().unwrap();
+        let failed = batch.failures().iter().map(|(i, _)| *i).collect::<Vec<_>>();
+        assert_eq!(failed, [1, 3]);
+        assert_eq!(&a[..], &expected[..16]);
+        assert_eq!(&c[..], &expected[100..116]);
+
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_read_vectored() {
+        const SIZE: usize = 20_000;
+        let arg = format!("{}", SIZE);
+        let (mut child, handle, addr, _) = spawn_test_process(Some(&[&arg])).unwrap();
+
+        // 1000 reads of varying sizes, scattered across the buffer.
+        let ranges = (0..1000)
+            .map(|i| ((i * 7919) % (SIZE - 64), 8 + i % 57))
+            .collect::<Vec<_>>();
+        let mut bufs = ranges
+            .iter()
+            .map(|(_, len)| vec![0u8; *len])
+            .collect::<Vec<_>>();
+        let mut reads = ranges
+            .iter()
+            .zip(bufs.iter_mut())
+            .map(|((offset, _), buf)| ReadOp::new(addr + offset, buf))
+            .collect::<Vec<_>>();
+        // Throw in a few bad addresses, which should only fail their own read.
+        let mut bad = [[0u8; 8]; 3];
+        for (i, buf) in bad.iter_mut().enumerate() {
+            reads.insert(i * 400, ReadOp::new(0x10 + i * 8, buf));
+        }
+        handle.copy_address_vectored(&mut reads).unwrap();
+        for (i, read) in reads.iter().enumerate() {
+            assert_eq!(read.error.is_some(), i % 400 == 0, "read {}", i);
+        }
+        drop(reads);
+
+        for ((offset, len), buf) in ranges.iter().zip(bufs.iter()) {
+            assert_eq!(buf, &copy_address(addr + offset, *len, &handle).unwrap());
+        }
+
+        child.wait().unwrap();
+
+        // Once the process is gone the batch as a whole fails.
+        #[cfg(target_os = "linux")]
+        {
+            let mut a = [0u8; 8];
+            let mut reads = [ReadOp::new(addr, &mut a)];
+            assert_eq!(
+                handle
+                    .copy_address_vectored(&mut reads)
+                    .unwrap_err()
+                    .raw_os_error(),
+                Some(libc::ESRCH)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_read_proc_mem() {
+        const SIZE: usize = 20_000;
+        let arg = format!("{}", SIZE);
+        let (mut child, handle, addr, _) = spawn_test_process(Some(&[&arg])).unwrap();
+        let expected = expected_pattern(SIZE);
+
+        let mut buf = vec![0; SIZE];
+        assert_eq!(handle.read_proc_mem(addr, &mut buf).unwrap(), SIZE);
+        assert_eq!(buf, expected);
+
+        // Clones reuse the file that is already open.
+        let clone = handle.clone();
+        let mut buf = vec![0; 100];
+        assert_eq!(clone.read_proc_mem(addr + 50, &mut buf).unwrap(), 100);
+        assert_eq!(buf, &expected[50..150]);
+
+        // Once the process is gone the file can't be read any more.
+        child.wait().unwrap();
+        assert!(handle.read_proc_mem(addr, &mut buf).is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_forced_proc_mem() {
+        const SIZE: usize = 20_000;
+        let arg = format!("{}", SIZE);
+        let (mut child, mut handle, addr, _) = spawn_test_process(Some(&[&arg])).unwrap();
+        handle.force_proc_mem();
+        let expected = expected_pattern(SIZE);
+        assert_eq!(copy_address(addr, SIZE, &handle).unwrap(), expected);
+
+        // Threads share the file without getting in each other's way.
+        let threads = (0..4)
+            .map(|i| {
+                let handle = handle.clone();
+                let expected = expected.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        let offset = i * 1000;
+                        let mem = copy_address(addr + offset, 5000, &handle).unwrap();
+                        assert_eq!(mem, &expected[offset..offset + 5000]);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut a = [0; 10];
+        let mut b = [0; 10];
+        let mut reads = [ReadOp::new(addr, &mut a), ReadOp::new(0, &mut b)];
+        handle.copy_address_vectored(&mut reads).unwrap();
+        assert!(reads[0].error.is_none());
+        assert!(reads[1].error.is_some());
+        assert_eq!(a, &expected[..10]);
+
+        // Writes go through the file too.
+        put_address(addr, &[9, 8, 7, 6], &handle).unwrap();
+        assert_eq!(copy_address(addr, 4, &handle).unwrap(), [9, 8, 7, 6]);
+        assert!(put_address(0, &[1], &handle).is_err());
+        child.wait().unwrap();
+
+        let arg = format!("{}", SIZE);
+        let (mut child, mut handle, addr, _) =
+            spawn_test_process(Some(&[&arg, "end-of-mapping"])).unwrap();
+        handle.force_proc_mem();
+        let mem = copy_address_lossy(addr + SIZE - 100, 200, &handle).unwrap();
+        assert_eq!(mem, &expected[SIZE - 100..]);
+        child.wait().unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_read_thread_id() {
+        // Threads other than the main one have no pidfd, but can still be
+        // read from by their ID.
+        let data = [1u8, 2, 3, 4];
+        let (tid_tx, tid_rx) = std::sync::mpsc::channel();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            tid_tx
+                .send(unsafe { libc::syscall(libc::SYS_gettid) } as Pid)
+                .unwrap();
+            let _ = done_rx.recv();
+        });
+        let tid = tid_rx.recv().unwrap();
+        assert_ne!(tid, unsafe { libc::getpid() });
+        let handle = ProcessHandle::try_from(tid).unwrap();
+        assert_eq!(
+            copy_address(data.as_ptr() as usize, 4, &handle).unwrap(),
+            data
+        );
+        drop(done_tx);
+        thread.join().unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_is_alive() {
+        for &pidfd in &[true, false] {
+            let (mut child, mut handle, addr, size) = spawn_test_process(None).unwrap();
+            if !pidfd {
+                handle.without_pidfd();
+            }
+            assert!(handle.is_alive());
+            assert!(copy_address(addr, size, &handle).is_ok());
+            child.wait().unwrap();
+            assert!(!handle.is_alive());
+            assert!(copy_address(addr, size, &handle).is_err());
+            let e = put_address(addr, &[1, 2, 3, 4], &handle).unwrap_err();
+            assert_eq!(e.raw_os_error(), Some(libc::ESRCH));
+            assert!(handle.memory_regions().is_err());
+        }
+    }
+
+    #[test]
+    fn test_memory_reader() {
+        const SIZE: usize = 20_000;
+        let arg = format!("{}", SIZE);
+        let (mut child, handle, addr, _) = spawn_test_process(Some(&[&arg])).unwrap();
+        let expected = expected_pattern(SIZE);
+
+        // Reads of 333 bytes through a 1000 byte buffer keep straddling the
+        // buffer boundary.
+        let reader = ProcessMemoryReader::new(handle.clone(), addr);
+        let mut reader = BufReader::with_capacity(1000, reader);
+        let mut mem = Vec::new();
+        let mut chunk = [0u8; 333];
+        while mem.len() + chunk.len() <= SIZE {
+            reader.read_exact(&mut chunk).unwrap();
+            mem.extend_from_slice(&chunk);
+        }
+        assert_eq!(mem, &expected[..mem.len()]);
+
+        reader.seek(SeekFrom::Start(10_000)).unwrap();
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(&chunk[..], &expected[10_000..10_333]);
+        reader.seek(SeekFrom::Current(-666)).unwrap();
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(&chunk[..], &expected[9_667..10_000]);
+        assert_eq!(
+            reader.seek(SeekFrom::End(0)).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        // With a length, reading to the end stops there.
+        let mut reader = ProcessMemoryReader::with_len(handle, addr, SIZE);
+        reader.seek(SeekFrom::End(-5000)).unwrap();
+        let mut mem = Vec::new();
+        BufReader::new(reader).read_to_end(&mut mem).unwrap();
+        assert_eq!(mem, &expected[SIZE - 5000..]);
+
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_memory_reader_end_of_mapping() {
+        const SIZE: usize = 1000;
+        let arg = format!("{}", SIZE);
+        let (mut child, handle, addr, _) =
+            spawn_test_process(Some(&[&arg, "end-of-mapping"])).unwrap();
+
+        let mut reader = ProcessMemoryReader::new(handle, addr);
+        let mut buf = vec![0; SIZE * 2];
+        assert_eq!(reader.read(&mut buf).unwrap(), SIZE);
+        assert_eq!(&buf[..SIZE], &expected_pattern(SIZE)[..]);
+        assert_eq!(reader.position(), SIZE as u64);
+        assert!(reader.read(&mut buf).is_err());
+
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_memory_regions() {
+        let (mut child, handle, addr, _) = spawn_test_process(None).unwrap();
+
+        let regions = handle.memory_regions().unwrap();
+        for pair in regions.windows(2) {
+            assert!(pair[0].end() <= pair[1].start, "{:#?}", pair);
+        }
+        let containing = regions
+            .iter()
+            .filter(|region| region.contains(addr))
+            .collect::<Vec<_>>();
+        assert_eq!(containing.len(), 1, "{:#?}", regions);
+        assert!(containing[0].readable);
+        assert!(containing[0].writable);
+
+        // The test program's own executable is mapped somewhere.
+        let exe = test_process_path().unwrap();
+        assert!(
+            regions.iter().any(|region| region.executable
+                && region.path.as_ref().and_then(|p| p.file_name()) == exe.file_name()),
+            "{:#?}",
+            regions
+        );
+
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        const SIZE: usize = 20_000;
+        let arg = format!("{}", SIZE);
+        let (mut child, handle, addr, _) = spawn_test_process(Some(&[&arg])).unwrap();
+        let handle = if cfg!(windows) {
+            ProcessHandle::open_writable(child.id() as Pid).unwrap()
+        } else {
+            handle
+        };
+
+        let pattern = (0..SIZE).map(|v| (v * 7 % 251) as u8).collect::<Vec<u8>>();
+        put_address(addr, &pattern, &handle).unwrap();
+        assert_eq!(copy_address(addr, SIZE, &handle).unwrap(), pattern);
+        write_address(addr, &[1, 2, 3, 4], &handle).unwrap();
+        assert_eq!(copy_address(addr, 4, &handle).unwrap(), [1, 2, 3, 4]);
+
+        // Writing somewhere unmapped fails.
+        assert!(handle.put_address(0x10, &pattern[..16]).is_err());
+
+        child.wait().unwrap();
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_open_with_access() {
+        use winapi::um::winnt::PROCESS_VM_READ;
+
+        let (mut child, _, addr, size) = spawn_test_process(None).unwrap();
+        let handle = ProcessHandle::open_with_access(child.id() as Pid, PROCESS_VM_READ).unwrap();
+        assert_eq!(
+            copy_address(addr, size, &handle).unwrap(),
+            expected_pattern(size)
+        );
+        // Listing regions needs `PROCESS_QUERY_INFORMATION`.
+        assert!(handle.memory_regions().is_err());
+        child.wait().unwrap();
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_write_read_only_handle() {
+        // Handles from `TryFrom` can't write without `PROCESS_VM_WRITE`.
+        let (mut child, handle, addr, _) = spawn_test_process(None).unwrap();
+        let e = put_address(addr, &[1, 2, 3, 4], &handle).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
+        child.wait().unwrap();
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_child_handle_drop_order() {
+        use std::os::windows::io::AsRawHandle;
+        use winapi::um::processthreadsapi::GetExitCodeProcess;
+
+        // Dropping the handle first must leave the `Child` usable.
+        let (mut child, handle, _, _) = spawn_test_process(None).unwrap();
+        drop(handle);
+        assert!(child.wait().unwrap().success());
+
+        // And dropping the `Child` first must leave the handle usable.
+        let (mut child, handle, _, _) = spawn_test_process(None).unwrap();
+        assert!(child.wait().unwrap().success());
+        drop(child);
+        let mut code = 1;
+        assert_ne!(
+            unsafe { GetExitCodeProcess(handle.as_raw_handle(), &mut code) },
+            0
+        );
+        assert_eq!(code, 0);
     }
 }